@@ -1,19 +1,39 @@
 use crate::{
     client::PlaneClient,
-    database::{connect::ConnectError, PlaneDatabase},
+    database::{action_queue::ActionQueue, connect::ConnectError, PlaneDatabase},
     names::{AnyNodeName, ControllerName},
     typed_socket::Handshake,
-    types::{ClusterName, ConnectRequest, ConnectResponse, NodeId},
+    types::{ClusterName, ConnectRequest, ConnectResponse, KeyConfig, NodeId},
 };
-use std::net::IpAddr;
+use std::time::Duration;
+use dashmap::DashMap;
+use std::{net::IpAddr, sync::Arc};
+use tokio::sync::broadcast;
 use url::Url;
 
+/// Dedup key for in-flight [`Controller::connect`] calls: a spawn is keyed by
+/// its target cluster and requested key.
+type ConnectDedupKey = (ClusterName, KeyConfig);
+
+/// How long an action lease may be held before the sweeper re-queues it.
+const ACTION_LEASE_TIMEOUT_SECONDS: i32 = 30;
+
+/// How often the action-queue sweeper runs.
+const ACTION_SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
 #[derive(Clone)]
 pub struct Controller {
     pub db: PlaneDatabase,
     pub id: ControllerName,
     pub client: PlaneClient,
     pub default_cluster: Option<ClusterName>,
+    /// Broadcast senders for `connect` calls that are currently in flight,
+    /// keyed so that concurrent callers for the same key coalesce onto one
+    /// spawn instead of racing to create duplicates. A broadcast channel fans
+    /// the single outcome out to every waiter, unlike an MPMC queue where only
+    /// one waiter would observe it.
+    in_flight_connects:
+        Arc<DashMap<ConnectDedupKey, broadcast::Sender<Result<ConnectResponse, ConnectError>>>>,
 }
 
 pub struct NodeHandle {
@@ -69,11 +89,20 @@ impl Controller {
     ) -> Self {
         let client = PlaneClient::new(controller_url);
 
+        // Launch the durable action-queue sweeper once per controller, so rows
+        // whose lease expired after a drone disconnect are redelivered.
+        ActionQueue::spawn_sweeper(
+            db.clone(),
+            ACTION_LEASE_TIMEOUT_SECONDS,
+            ACTION_SWEEP_INTERVAL,
+        );
+
         Self {
             db,
             id,
             client,
             default_cluster,
+            in_flight_connects: Arc::new(DashMap::new()),
         }
     }
 
@@ -81,11 +110,91 @@ impl Controller {
         &self,
         connect_request: &ConnectRequest,
     ) -> Result<ConnectResponse, ConnectError> {
-        let response = self
-            .db
+        // Without a key there is nothing to coalesce on: every call spawns a
+        // fresh backend, so go straight to the database.
+        let Some(dedup_key) = self.connect_dedup_key(connect_request) else {
+            return self.do_connect(connect_request).await;
+        };
+
+        // Fast path: another call for this key is already in flight. Subscribe
+        // to its broadcast and wait on the outcome instead of hitting the
+        // database. A `recv` error means the channel closed without delivering
+        // a value to us: the leader dropped the sender without broadcasting
+        // (panic/cancel), or it broadcast before we subscribed. Fall through to
+        // driving the connect ourselves in that case.
+        if let Some(sender) = self.in_flight_connects.get(&dedup_key).map(|s| s.clone()) {
+            let mut receiver = sender.subscribe();
+            drop(sender);
+            if let Ok(result) = receiver.recv().await {
+                return result;
+            }
+        }
+
+        // Slow path: become the leader for this key. Use the entry API so the
+        // check-and-insert is atomic against other callers racing us here.
+        let sender = match self.in_flight_connects.entry(dedup_key.clone()) {
+            dashmap::mapref::entry::Entry::Occupied(entry) => {
+                // Someone beat us to it between the fast-path check and now.
+                let mut receiver = entry.get().subscribe();
+                drop(entry);
+                if let Ok(result) = receiver.recv().await {
+                    return result;
+                }
+                return self.do_connect(connect_request).await;
+            }
+            dashmap::mapref::entry::Entry::Vacant(entry) => {
+                let (sender, _) = broadcast::channel(1);
+                entry.insert(sender.clone());
+                sender
+            }
+        };
+
+        // Ensure the entry is removed even if the future below is cancelled or
+        // panics, so a failed attempt doesn't poison the key forever.
+        let _guard = InFlightGuard {
+            map: &self.in_flight_connects,
+            key: &dedup_key,
+        };
+
+        let result = self.do_connect(connect_request).await;
+
+        // Broadcast the outcome to every subscribed waiter. Each receiver
+        // observes the same cloned value; a send error just means no waiters
+        // are currently subscribed, which is fine.
+        let _ = sender.send(result.clone());
+
+        result
+    }
+
+    async fn do_connect(
+        &self,
+        connect_request: &ConnectRequest,
+    ) -> Result<ConnectResponse, ConnectError> {
+        self.db
             .connect(self.default_cluster.as_ref(), connect_request, &self.client)
-            .await?;
+            .await
+    }
+
+    fn connect_dedup_key(&self, connect_request: &ConnectRequest) -> Option<ConnectDedupKey> {
+        let key = connect_request.key.clone()?;
+        let cluster = connect_request
+            .spawn_config
+            .as_ref()
+            .and_then(|s| s.cluster.clone())
+            .or_else(|| self.default_cluster.clone())?;
+        Some((cluster, key))
+    }
+}
 
-        Ok(response)
+/// Removes an in-flight `connect` entry on drop, so a cancelled or panicking
+/// leader releases its key rather than poisoning it for future callers.
+struct InFlightGuard<'a> {
+    map: &'a DashMap<ConnectDedupKey, broadcast::Sender<Result<ConnectResponse, ConnectError>>>,
+    key: &'a ConnectDedupKey,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.map.remove(self.key);
     }
 }
@@ -0,0 +1,167 @@
+use super::{backend::BackendActionMessage, PlaneDatabase};
+use crate::types::NodeId;
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+
+/// Lifecycle status of a row in the `action_queue` work table.
+///
+/// A row starts as [`ActionQueueStatus::New`]; a drone claims it by atomically
+/// flipping it to [`ActionQueueStatus::Running`] and stamping the heartbeat. The
+/// sweeper returns rows whose lease has expired back to `New`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "action_queue_status", rename_all = "lowercase")]
+pub enum ActionQueueStatus {
+    New,
+    Running,
+}
+
+/// Durable, lease-based queue for [`BackendActionMessage`] delivery.
+///
+/// Postgres LISTEN/NOTIFY drops messages when no drone is connected at emit
+/// time, so a drone that reconnects can miss a spawn or terminate action. This
+/// queue persists each action in the `action_queue` table and hands it to the
+/// drone with at-least-once semantics that survive controller restarts and
+/// drone disconnects. The drone is made idempotent on `action_id`.
+pub struct ActionQueue<'a> {
+    db: &'a PlaneDatabase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaimedAction {
+    pub id: uuid::Uuid,
+    /// Logical queue the action was enqueued on (the backend id); carried
+    /// through so the drone can route the claimed job without re-parsing it.
+    pub queue: String,
+    pub message: BackendActionMessage,
+}
+
+impl<'a> ActionQueue<'a> {
+    pub fn new(db: &'a PlaneDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Durably enqueue an action for the owning drone. Runs in the caller's
+    /// transaction so the enqueue commits atomically with the state change that
+    /// produced it.
+    pub async fn enqueue<'e>(
+        executor: impl PgExecutor<'e>,
+        message: &BackendActionMessage,
+    ) -> sqlx::Result<()> {
+        let job = serde_json::to_value(message)
+            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+        sqlx::query!(
+            r#"
+            insert into action_queue (drone_id, queue, job)
+            values ($1, $2, $3)
+            "#,
+            message.drone_id.as_i32(),
+            message.backend_id.to_string(),
+            job,
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Atomically claim up to `limit` new actions for a drone, leasing them by
+    /// flipping them to `running` and stamping the heartbeat. Uses `FOR UPDATE
+    /// SKIP LOCKED` so concurrent claims from the same drone don't contend.
+    pub async fn claim(
+        &self,
+        drone_id: NodeId,
+        limit: i64,
+    ) -> sqlx::Result<Vec<ClaimedAction>> {
+        let rows = sqlx::query!(
+            r#"
+            update action_queue
+            set status = $1, heartbeat = now()
+            where id in (
+                select id from action_queue
+                where drone_id = $2 and status = $3
+                order by seq
+                limit $4
+                for update skip locked
+            )
+            returning id, queue, job
+            "#,
+            ActionQueueStatus::Running as ActionQueueStatus,
+            drone_id.as_i32(),
+            ActionQueueStatus::New as ActionQueueStatus,
+            limit,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let mut claimed = Vec::with_capacity(rows.len());
+        for row in rows {
+            let message: BackendActionMessage = serde_json::from_value(row.job)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            claimed.push(ClaimedAction {
+                id: row.id,
+                queue: row.queue.unwrap_or_default(),
+                message,
+            });
+        }
+
+        Ok(claimed)
+    }
+
+    /// Acknowledge a completed action by deleting its row.
+    pub async fn ack(&self, id: uuid::Uuid) -> sqlx::Result<()> {
+        sqlx::query!(
+            r#"
+            delete from action_queue
+            where id = $1
+            "#,
+            id,
+        )
+        .execute(&self.db.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Spawn the background sweeper that re-queues expired leases on an
+    /// interval. Launched once per controller at startup so rows orphaned by a
+    /// drone disconnect are redelivered without operator intervention.
+    pub fn spawn_sweeper(
+        db: PlaneDatabase,
+        lease_timeout_seconds: i32,
+        interval: std::time::Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let queue = ActionQueue::new(&db);
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match queue.sweep_expired(lease_timeout_seconds).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!(requeued = n, "Re-queued expired action leases."),
+                    Err(err) => tracing::error!(?err, "Failed to sweep expired action leases."),
+                }
+            }
+        })
+    }
+
+    /// Re-queue rows whose lease has expired so they are redelivered. Returns
+    /// the number of actions returned to `new`.
+    pub async fn sweep_expired(&self, lease_timeout_seconds: i32) -> sqlx::Result<u64> {
+        let result = sqlx::query!(
+            r#"
+            update action_queue
+            set status = $1, heartbeat = null
+            where status = $2
+                and heartbeat < now() - make_interval(secs => $3)
+            "#,
+            ActionQueueStatus::New as ActionQueueStatus,
+            ActionQueueStatus::Running as ActionQueueStatus,
+            lease_timeout_seconds,
+        )
+        .execute(&self.db.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
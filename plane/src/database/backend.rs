@@ -1,4 +1,6 @@
-use super::{subscribe::emit_with_key, util::MapSqlxError, PlaneDatabase};
+use super::{
+    action_queue::ActionQueue, subscribe::emit_with_key, PlaneDatabase,
+};
 use crate::{
     log_types::{BackendAddr, LoggableTime},
     names::{BackendActionName, BackendName},
@@ -6,7 +8,7 @@ use crate::{
     types::{BackendStatus, BearerToken, NodeId, SecretToken, TimestampedBackendStatus},
 };
 use chrono::{DateTime, Utc};
-use futures_util::Stream;
+use futures_util::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 
@@ -28,33 +30,66 @@ impl super::subscribe::NotificationPayload for BackendActionMessage {
     }
 }
 
-impl super::subscribe::NotificationPayload for BackendStatus {
-    fn kind() -> &'static str {
-        "backend_state"
-    }
-}
-
 impl<'a> BackendDatabase<'a> {
     pub fn new(db: &'a PlaneDatabase) -> Self {
         Self { db }
     }
 
+    /// Dispatch a backend action to its drone.
+    ///
+    /// The action is first enqueued durably in `action_queue` and then emitted
+    /// over LISTEN/NOTIFY as a best-effort live wakeup. Both happen in one
+    /// transaction, so a drone that is disconnected at emit time still receives
+    /// the action when it reconnects and claims its queue. Delivery is
+    /// at-least-once; the drone dedups on `action_id`.
+    pub async fn send_action(&self, message: &BackendActionMessage) -> sqlx::Result<()> {
+        let mut txn = self.db.pool.begin().await?;
+
+        ActionQueue::enqueue(&mut *txn, message).await?;
+        emit_with_key(&mut *txn, &message.drone_id.to_string(), message).await?;
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Build the live-notification transport. Always uses the default
+    /// configuration, i.e. the Postgres LISTEN/NOTIFY path;
+    /// [`NotificationTransportConfig::Redis`] is not selected here.
+    fn transport(&self) -> sqlx::Result<Box<dyn super::transport::NotificationTransport>> {
+        super::transport::NotificationTransportConfig::default().build(self.db)
+    }
+
     pub async fn status_stream(
         &self,
         backend: &BackendName,
     ) -> sqlx::Result<impl Stream<Item = TimestampedBackendStatus>> {
-        let mut sub = self
-            .db
-            .subscribe_with_key::<BackendStatus>(&backend.to_string());
-
+        // Subscribe to the live tail through the notification transport before reading history, so no event is missed in the gap. The channel
+        // carries `TimestampedBackendStatus`, matching what `update_status`
+        // publishes.
+        let mut live = self.transport()?.subscribe(&backend.to_string()).await?;
+
+        // The monotonic dedup is pushed into SQL: the window filter drops any
+        // row whose native-enum status is not strictly greater than every
+        // earlier row's (`status > prev_status`, where `prev_status` is the
+        // running max), so the history read already yields a strictly
+        // increasing sequence instead of relying on Rust-side comparison.
         let result = sqlx::query!(
             r#"
-            select
-                id,
-                created_at,
-                status
-            from backend_status
-            where backend_id = $1
+            select id, created_at, status as "status: BackendStatus"
+            from (
+                select
+                    id,
+                    created_at,
+                    status,
+                    max(status) over (
+                        order by id
+                        rows between unbounded preceding and 1 preceding
+                    ) as prev_status
+                from backend_status
+                where backend_id = $1
+            ) sub
+            where prev_status is null or status > prev_status
             order by id asc
             "#,
             backend.to_string(),
@@ -65,40 +100,27 @@ impl<'a> BackendDatabase<'a> {
         let stream = async_stream::stream! {
             let mut last_status = None;
             for row in result {
-                let status = BackendStatus::try_from(row.status);
-                match status {
-                    Ok(status) => {
-                        yield TimestampedBackendStatus {
-                            time: LoggableTime(row.created_at),
-                            status,
-                        };
-                        last_status = Some(status);
-                    }
-                    Err(e) => {
-                        tracing::warn!(?e, "Invalid backend status");
-                    }
-                }
+                // The column is a native `backend_status` enum, so it is always
+                // a valid status by construction.
+                let status = row.status;
+                yield TimestampedBackendStatus {
+                    time: LoggableTime(row.created_at),
+                    status,
+                };
+                last_status = Some(status);
             }
 
-            while let Some(item) = sub.next().await {
-                // In order to missing events that occur when we read the DB and when we subscribe to updates,
-                // we subscribe to updates before we read from the DB. But this means we might get duplicate
-                // events, so we keep track of the last status we saw and ignore events that have a status
-                // less than or equal to it.
+            while let Some(item) = live.next().await {
+                // We subscribe before reading history to avoid missing events in
+                // the gap, which can produce duplicates; drop any live status
+                // that isn't strictly newer than the last one we emitted.
                 if let Some(last_status) = last_status {
-                    if item.payload <= last_status {
+                    if item.status <= last_status {
                         continue;
                     }
                 }
 
-                let status = item.payload;
-                let time = item.timestamp;
-
-                let item = TimestampedBackendStatus {
-                    status,
-                    time: LoggableTime(time),
-                };
-
+                last_status = Some(item.status);
                 yield item;
             }
         };
@@ -112,7 +134,7 @@ impl<'a> BackendDatabase<'a> {
             select
                 id,
                 cluster,
-                last_status,
+                last_status as "last_status: BackendStatus",
                 last_status_time,
                 drone_id,
                 expiration_time,
@@ -135,7 +157,7 @@ impl<'a> BackendDatabase<'a> {
             id: BackendName::try_from(result.id)
                 .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?,
             cluster: result.cluster,
-            last_status: BackendStatus::try_from(result.last_status).map_sqlx_error()?,
+            last_status: result.last_status,
             last_status_time: result.last_status_time,
             last_keepalive: result.last_keepalive,
             drone_id: NodeId::from(result.drone_id),
@@ -154,7 +176,23 @@ impl<'a> BackendDatabase<'a> {
     ) -> sqlx::Result<()> {
         let mut txn = self.db.pool.begin().await?;
 
-        emit_with_key(&mut *txn, &backend.to_string(), &status).await?;
+        let previous = sqlx::query!(
+            r#"
+            select
+                last_status as "last_status: BackendStatus",
+                allowed_idle_seconds,
+                last_keepalive,
+                expiration_time,
+                now() as "as_of!"
+            from backend
+            where id = $1
+            "#,
+            backend.to_string(),
+        )
+        .fetch_optional(&mut *txn)
+        .await?;
+
+        let previous_status = previous.as_ref().map(|row| row.last_status);
 
         sqlx::query!(
             r#"
@@ -167,7 +205,7 @@ impl<'a> BackendDatabase<'a> {
             where id = $1
             "#,
             backend.to_string(),
-            status.to_string(),
+            status as BackendStatus,
             address.map(|a| a.0.to_string()),
             exit_code,
         )
@@ -180,7 +218,7 @@ impl<'a> BackendDatabase<'a> {
             values ($1, $2)
             "#,
             backend.to_string(),
-            status.to_string(),
+            status as BackendStatus,
         )
         .execute(&mut *txn)
         .await?;
@@ -200,6 +238,37 @@ impl<'a> BackendDatabase<'a> {
 
         txn.commit().await?;
 
+        // Publish the live update through the transport after the row is
+        // committed, so subscribers only observe persisted state. The payload
+        // is a `TimestampedBackendStatus`, matching what `status_stream`
+        // subscribes to on the `backend_state` channel.
+        self.transport()?
+            .publish(
+                &backend.to_string(),
+                &TimestampedBackendStatus {
+                    status,
+                    time: LoggableTime(Utc::now()),
+                },
+            )
+            .await?;
+
+        crate::metrics::record_status_transition(previous_status, status);
+
+        // Count the termination once, at the actual transition to `Terminated`,
+        // attributing it to whichever limit actually fired and to `other` when
+        // neither did (so manual stops and self-exits aren't mislabeled).
+        if status == BackendStatus::Terminated {
+            if let Some(previous) = previous {
+                let reason = crate::metrics::TerminationReason::attribute(
+                    previous.allowed_idle_seconds,
+                    previous.last_keepalive,
+                    previous.expiration_time,
+                    previous.as_of,
+                );
+                crate::metrics::record_termination(reason);
+            }
+        }
+
         Ok(())
     }
 
@@ -209,7 +278,7 @@ impl<'a> BackendDatabase<'a> {
             select
                 id,
                 cluster,
-                last_status,
+                last_status as "last_status: BackendStatus",
                 last_status_time,
                 drone_id,
                 expiration_time,
@@ -229,7 +298,7 @@ impl<'a> BackendDatabase<'a> {
                 id: BackendName::try_from(row.id)
                     .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?,
                 cluster: row.cluster,
-                last_status: BackendStatus::try_from(row.last_status).map_sqlx_error()?,
+                last_status: row.last_status,
                 last_status_time: row.last_status_time,
                 last_keepalive: row.last_keepalive,
                 drone_id: NodeId::from(row.drone_id),
@@ -239,6 +308,21 @@ impl<'a> BackendDatabase<'a> {
             });
         }
 
+        // Refresh the per-cluster/status gauge from the authoritative row set.
+        // `list_backends` is the controller's periodic census, so this keeps
+        // the gauge current without maintaining deltas on every transition.
+        // Reset first so a (cluster, status) pair that has dropped to zero is
+        // cleared rather than left pinned at its previous value.
+        crate::metrics::reset_backend_counts();
+        let mut counts: std::collections::HashMap<(&str, BackendStatus), i64> =
+            std::collections::HashMap::new();
+        for row in &result {
+            *counts.entry((row.cluster.as_str(), row.last_status)).or_insert(0) += 1;
+        }
+        for ((cluster, status), count) in counts {
+            crate::metrics::set_backend_count(cluster, status, count);
+        }
+
         Ok(result)
     }
 
@@ -331,21 +415,32 @@ impl<'a> BackendDatabase<'a> {
                 )
             "#,
             drone_id.as_i32(),
-            BackendStatus::Terminated.to_string(),
+            BackendStatus::Terminated as BackendStatus,
         )
         .fetch_all(&self.db.pool)
         .await?;
 
         let mut candidates = Vec::new();
         for row in result {
-            candidates.push(TerminationCandidate {
+            let candidate = TerminationCandidate {
                 backend_id: BackendName::try_from(row.backend_id)
                     .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?,
                 expiration_time: row.expiration_time,
                 last_keepalive: row.last_keepalive,
                 allowed_idle_seconds: row.allowed_idle_seconds,
                 as_of: row.as_of,
-            });
+            };
+
+            // Sample keepalive lag during the sweep. The termination counter is
+            // *not* incremented here: this is a read-only enumeration of
+            // candidates that re-runs every poll, so counting here would
+            // over-count backends that stay candidates across polls or are
+            // never actually terminated. The count is recorded on the real
+            // `Terminated` transition in `update_status` instead.
+            let lag = candidate.as_of - candidate.last_keepalive;
+            crate::metrics::observe_keepalive_lag(lag.num_milliseconds() as f64 / 1000.0);
+
+            candidates.push(candidate);
         }
 
         Ok(candidates)
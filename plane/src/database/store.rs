@@ -0,0 +1,550 @@
+use super::{
+    backend::{BackendRow, TerminationCandidate},
+    subscribe::Subscription,
+};
+use super::{backend::BackendDatabase, PlaneDatabase};
+use crate::{
+    log_types::BackendAddr,
+    names::{AnyNodeName, BackendName, ControllerName},
+    protocol::RouteInfo,
+    types::{BackendStatus, BearerToken, ClusterName, NodeId, NodeKind, SecretToken, TimestampedBackendStatus},
+    PlaneVersionInfo,
+};
+use async_trait::async_trait;
+use futures_util::Stream;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+
+/// Storage backend for the controller's durable state.
+///
+/// The production deployment is backed by Postgres (LISTEN/NOTIFY fan-out,
+/// `FOR UPDATE SKIP LOCKED`, `make_interval`); the embedded SQLite backend
+/// mirrors the same surface for single-node dev/CI runs with no external
+/// services, fanning notifications out in-process instead of through the
+/// database. New storage methods should be added here so both backends stay in
+/// lockstep.
+#[async_trait]
+pub trait Store: Send + Sync + 'static {
+    /// Register a node (drone, proxy, or controller) and return its id.
+    async fn register(
+        &self,
+        cluster: Option<&ClusterName>,
+        name: &AnyNodeName,
+        kind: NodeKind,
+        controller: &ControllerName,
+        version: &PlaneVersionInfo,
+        ip: IpAddr,
+    ) -> sqlx::Result<NodeId>;
+
+    /// Record a new backend status, update the live `backend` row, and publish
+    /// the transition to subscribers.
+    async fn update_status(
+        &self,
+        backend: &BackendName,
+        status: BackendStatus,
+        address: Option<BackendAddr>,
+        exit_code: Option<i32>,
+    ) -> sqlx::Result<()>;
+
+    /// Stream the status history of a backend followed by live updates,
+    /// deduplicating the overlap by the last status observed. Implementations
+    /// may drop history rows that are not newer than an earlier status.
+    async fn status_stream(
+        &self,
+        backend: &BackendName,
+    ) -> sqlx::Result<Pin<Box<dyn Stream<Item = TimestampedBackendStatus> + Send>>>;
+
+    /// Backends on `drone_id` that have exceeded their idle or lifetime limit.
+    async fn termination_candidates(
+        &self,
+        drone_id: NodeId,
+    ) -> sqlx::Result<Vec<TerminationCandidate>>;
+
+    /// Resolve a bearer token to the route it addresses, if any.
+    async fn route_info_for_token(
+        &self,
+        token: &BearerToken,
+    ) -> sqlx::Result<Option<RouteInfo>>;
+
+    /// Fetch a single backend row by name.
+    async fn backend(&self, backend_id: &BackendName) -> sqlx::Result<Option<BackendRow>>;
+}
+
+/// In-process notification fan-out used by [`SqliteStore`] in place of
+/// Postgres LISTEN/NOTIFY. Subscribers are keyed the same way as the Postgres
+/// path (`backend.to_string()`), so the subscribe-before-read ordering in
+/// `status_stream` is preserved.
+#[derive(Clone, Default)]
+pub struct LocalNotifier {
+    channels: std::sync::Arc<dashmap::DashMap<String, tokio::sync::broadcast::Sender<TimestampedBackendStatus>>>,
+}
+
+impl LocalNotifier {
+    pub fn publish(&self, key: &str, payload: TimestampedBackendStatus) {
+        if let Some(sender) = self.channels.get(key) {
+            // A send error just means there are no live subscribers, which is
+            // fine: they will read the row from the table when they attach.
+            let _ = sender.send(payload);
+        }
+    }
+
+    pub fn subscribe(&self, key: &str) -> Subscription<BackendStatus> {
+        let sender = self
+            .channels
+            .entry(key.to_string())
+            .or_insert_with(|| tokio::sync::broadcast::channel(1024).0)
+            .clone();
+        Subscription::from_broadcast(sender.subscribe())
+    }
+}
+
+/// Embedded SQLite [`Store`] for single-node and test deployments.
+///
+/// SQLite lacks LISTEN/NOTIFY, `FOR UPDATE SKIP LOCKED`, and `make_interval`,
+/// so notifications are fanned out through a [`LocalNotifier`] and the
+/// interval arithmetic in [`Store::termination_candidates`] is expressed with
+/// SQLite's `julianday` function instead.
+pub struct SqliteStore {
+    pool: sqlx::SqlitePool,
+    notifier: LocalNotifier,
+}
+
+impl SqliteStore {
+    pub fn new(pool: sqlx::SqlitePool) -> Self {
+        Self {
+            pool,
+            notifier: LocalNotifier::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn register(
+        &self,
+        cluster: Option<&ClusterName>,
+        name: &AnyNodeName,
+        kind: NodeKind,
+        controller: &ControllerName,
+        version: &PlaneVersionInfo,
+        ip: IpAddr,
+    ) -> sqlx::Result<NodeId> {
+        // `last_insert_rowid()` is not set when an upsert takes the UPDATE
+        // branch (the drone-reconnect case), so a re-registering node would get
+        // a stale id. SQLite has supported `RETURNING` since 3.35, so read the
+        // row's real id back from both branches.
+        let (id,): (i64,) = sqlx::query_as(
+            r#"
+            insert into node (name, cluster, kind, controller, plane_version, plane_hash, ip)
+            values (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            on conflict (name) do update set
+                cluster = excluded.cluster,
+                controller = excluded.controller,
+                plane_version = excluded.plane_version,
+                plane_hash = excluded.plane_hash,
+                ip = excluded.ip
+            returning id
+            "#,
+        )
+        .bind(name.to_string())
+        .bind(cluster.map(|c| c.to_string()))
+        .bind(kind.to_string())
+        .bind(controller.to_string())
+        .bind(&version.version)
+        .bind(&version.git_hash)
+        .bind(ip.to_string())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(NodeId::from(id as i32))
+    }
+
+    async fn update_status(
+        &self,
+        backend: &BackendName,
+        status: BackendStatus,
+        address: Option<BackendAddr>,
+        exit_code: Option<i32>,
+    ) -> sqlx::Result<()> {
+        let mut txn = self.pool.begin().await?;
+
+        // Read the prior row so the SQLite path records the same lifecycle and
+        // termination metrics as the Postgres path (see the `Store` doc: both
+        // backends must stay in lockstep).
+        let previous = sqlx::query_as::<_, PreviousStatusRow>(
+            r#"
+            select
+                last_status,
+                allowed_idle_seconds,
+                last_keepalive,
+                expiration_time,
+                current_timestamp as as_of
+            from backend
+            where id = ?1
+            "#,
+        )
+        .bind(backend.to_string())
+        .fetch_optional(&mut *txn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            update backend
+            set
+                last_status = ?2,
+                last_status_time = current_timestamp,
+                cluster_address = ?3,
+                exit_code = ?4
+            where id = ?1
+            "#,
+        )
+        .bind(backend.to_string())
+        .bind(status.to_string())
+        .bind(address.map(|a| a.0.to_string()))
+        .bind(exit_code)
+        .execute(&mut *txn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            insert into backend_status (backend_id, status)
+            values (?1, ?2)
+            "#,
+        )
+        .bind(backend.to_string())
+        .bind(status.to_string())
+        .execute(&mut *txn)
+        .await?;
+
+        if status == BackendStatus::Terminated {
+            sqlx::query("delete from backend_key where id = ?1")
+                .bind(backend.to_string())
+                .execute(&mut *txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        let previous_status = previous
+            .as_ref()
+            .and_then(|row| BackendStatus::try_from(row.last_status.clone()).ok());
+        crate::metrics::record_status_transition(previous_status, status);
+
+        if status == BackendStatus::Terminated {
+            if let Some(previous) = &previous {
+                let reason = crate::metrics::TerminationReason::attribute(
+                    previous.allowed_idle_seconds,
+                    previous.last_keepalive,
+                    previous.expiration_time,
+                    previous.as_of,
+                );
+                crate::metrics::record_termination(reason);
+            }
+        }
+
+        self.notifier.publish(
+            &backend.to_string(),
+            TimestampedBackendStatus {
+                status,
+                time: crate::log_types::LoggableTime(chrono::Utc::now()),
+            },
+        );
+
+        Ok(())
+    }
+
+    async fn status_stream(
+        &self,
+        backend: &BackendName,
+    ) -> sqlx::Result<Pin<Box<dyn Stream<Item = TimestampedBackendStatus> + Send>>> {
+        let mut sub = self.notifier.subscribe(&backend.to_string());
+
+        let rows = sqlx::query_as::<_, (String, chrono::DateTime<chrono::Utc>)>(
+            r#"
+            select status, created_at
+            from backend_status
+            where backend_id = ?1
+            order by id asc
+            "#,
+        )
+        .bind(backend.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let stream = async_stream::stream! {
+            let mut last_status = None;
+            for (status, created_at) in rows {
+                match BackendStatus::try_from(status) {
+                    Ok(status) => {
+                        yield TimestampedBackendStatus {
+                            time: crate::log_types::LoggableTime(created_at),
+                            status,
+                        };
+                        last_status = Some(status);
+                    }
+                    Err(e) => tracing::warn!(?e, "Invalid backend status"),
+                }
+            }
+
+            while let Some(item) = sub.next().await {
+                if let Some(last_status) = last_status {
+                    if item.payload <= last_status {
+                        continue;
+                    }
+                }
+                last_status = Some(item.payload);
+                yield TimestampedBackendStatus {
+                    status: item.payload,
+                    time: crate::log_types::LoggableTime(item.timestamp),
+                };
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn termination_candidates(
+        &self,
+        drone_id: NodeId,
+    ) -> sqlx::Result<Vec<TerminationCandidate>> {
+        // SQLite has no `make_interval`; express the idle window as a
+        // difference of Julian days (`allowed_idle_seconds / 86400.0`) instead.
+        let rows = sqlx::query_as::<_, TerminationCandidateRow>(
+            r#"
+            select
+                id as backend_id,
+                expiration_time,
+                allowed_idle_seconds,
+                last_keepalive,
+                current_timestamp as as_of
+            from backend
+            where
+                drone_id = ?1
+                and last_status != ?2
+                and (
+                    julianday('now') - julianday(last_keepalive)
+                        > allowed_idle_seconds / 86400.0
+                    or julianday('now') > julianday(expiration_time)
+                )
+            "#,
+        )
+        .bind(drone_id.as_i32())
+        .bind(BackendStatus::Terminated.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut candidates = Vec::with_capacity(rows.len());
+        for row in rows {
+            candidates.push(TerminationCandidate {
+                backend_id: BackendName::try_from(row.backend_id)
+                    .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?,
+                expiration_time: row.expiration_time,
+                last_keepalive: row.last_keepalive,
+                allowed_idle_seconds: row.allowed_idle_seconds,
+                as_of: row.as_of,
+            });
+        }
+
+        Ok(candidates)
+    }
+
+    async fn route_info_for_token(
+        &self,
+        token: &BearerToken,
+    ) -> sqlx::Result<Option<RouteInfo>> {
+        let row = sqlx::query_as::<_, RouteInfoRow>(
+            r#"
+            select
+                backend_id,
+                username,
+                auth,
+                cluster_address,
+                secret_token
+            from token
+            left join backend
+            on backend.id = token.backend_id
+            where token = ?1
+            limit 1
+            "#,
+        )
+        .bind(token.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let Some(address) = row.cluster_address else {
+            return Ok(None);
+        };
+
+        let Ok(address) = address.parse::<SocketAddr>() else {
+            tracing::warn!("Invalid cluster address: {}", address);
+            return Ok(None);
+        };
+
+        Ok(Some(RouteInfo {
+            backend_id: BackendName::try_from(row.backend_id)
+                .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?,
+            address: BackendAddr(address),
+            secret_token: SecretToken::from(row.secret_token),
+            user: row.username,
+            user_data: Some(
+                serde_json::from_str(&row.auth)
+                    .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            ),
+        }))
+    }
+
+    async fn backend(&self, backend_id: &BackendName) -> sqlx::Result<Option<BackendRow>> {
+        let row = sqlx::query_as::<_, BackendSqliteRow>(
+            r#"
+            select
+                id,
+                cluster,
+                last_status,
+                last_status_time,
+                drone_id,
+                expiration_time,
+                allowed_idle_seconds,
+                last_keepalive,
+                current_timestamp as as_of
+            from backend
+            where id = ?1
+            "#,
+        )
+        .bind(backend_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(BackendRow {
+            id: BackendName::try_from(row.id)
+                .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?,
+            cluster: row.cluster,
+            last_status: BackendStatus::try_from(row.last_status)
+                .map_err(|e| sqlx::Error::Decode(e.into()))?,
+            last_status_time: row.last_status_time,
+            last_keepalive: row.last_keepalive,
+            drone_id: NodeId::from(row.drone_id),
+            expiration_time: row.expiration_time,
+            allowed_idle_seconds: row.allowed_idle_seconds,
+            as_of: row.as_of,
+        }))
+    }
+}
+
+/// Production [`Store`] backed by Postgres. Delegates to the existing
+/// [`BackendDatabase`]/node query methods so the trait and the direct call
+/// sites share one implementation of each query.
+pub struct PostgresStore {
+    db: PlaneDatabase,
+}
+
+impl PostgresStore {
+    pub fn new(db: PlaneDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl Store for PostgresStore {
+    async fn register(
+        &self,
+        cluster: Option<&ClusterName>,
+        name: &AnyNodeName,
+        kind: NodeKind,
+        controller: &ControllerName,
+        version: &PlaneVersionInfo,
+        ip: IpAddr,
+    ) -> sqlx::Result<NodeId> {
+        self.db
+            .node()
+            .register(cluster, name, kind, controller, version, ip)
+            .await
+    }
+
+    async fn update_status(
+        &self,
+        backend: &BackendName,
+        status: BackendStatus,
+        address: Option<BackendAddr>,
+        exit_code: Option<i32>,
+    ) -> sqlx::Result<()> {
+        BackendDatabase::new(&self.db)
+            .update_status(backend, status, address, exit_code)
+            .await
+    }
+
+    async fn status_stream(
+        &self,
+        backend: &BackendName,
+    ) -> sqlx::Result<Pin<Box<dyn Stream<Item = TimestampedBackendStatus> + Send>>> {
+        let stream = BackendDatabase::new(&self.db).status_stream(backend).await?;
+        Ok(Box::pin(stream))
+    }
+
+    async fn termination_candidates(
+        &self,
+        drone_id: NodeId,
+    ) -> sqlx::Result<Vec<TerminationCandidate>> {
+        BackendDatabase::new(&self.db)
+            .termination_candidates(drone_id)
+            .await
+    }
+
+    async fn route_info_for_token(
+        &self,
+        token: &BearerToken,
+    ) -> sqlx::Result<Option<RouteInfo>> {
+        BackendDatabase::new(&self.db).route_info_for_token(token).await
+    }
+
+    async fn backend(&self, backend_id: &BackendName) -> sqlx::Result<Option<BackendRow>> {
+        BackendDatabase::new(&self.db).backend(backend_id).await
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PreviousStatusRow {
+    last_status: String,
+    allowed_idle_seconds: Option<i32>,
+    last_keepalive: chrono::DateTime<chrono::Utc>,
+    expiration_time: Option<chrono::DateTime<chrono::Utc>>,
+    as_of: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct TerminationCandidateRow {
+    backend_id: String,
+    expiration_time: Option<chrono::DateTime<chrono::Utc>>,
+    allowed_idle_seconds: Option<i32>,
+    last_keepalive: chrono::DateTime<chrono::Utc>,
+    as_of: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RouteInfoRow {
+    backend_id: String,
+    username: Option<String>,
+    auth: String,
+    cluster_address: Option<String>,
+    secret_token: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct BackendSqliteRow {
+    id: String,
+    cluster: String,
+    last_status: String,
+    last_status_time: chrono::DateTime<chrono::Utc>,
+    drone_id: i32,
+    expiration_time: Option<chrono::DateTime<chrono::Utc>>,
+    allowed_idle_seconds: Option<i32>,
+    last_keepalive: chrono::DateTime<chrono::Utc>,
+    as_of: chrono::DateTime<chrono::Utc>,
+}
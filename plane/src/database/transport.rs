@@ -0,0 +1,177 @@
+use super::{subscribe::NotificationPayload, PlaneDatabase};
+use crate::types::TimestampedBackendStatus;
+use async_trait::async_trait;
+use futures_util::{Stream, StreamExt};
+use std::pin::Pin;
+
+/// Transport for keyed status notifications backing
+/// [`status_stream`](super::backend::BackendDatabase::status_stream).
+///
+/// Postgres LISTEN/NOTIFY funnels every `backend_state`/`backend_action`
+/// notification through the single primary database, capping fan-out as
+/// controller replicas multiply. This trait lets the high-frequency live path
+/// be served by Redis pub/sub instead, offloading the primary so many
+/// proxies/controllers can each tail thousands of `status_stream`s. History is
+/// always read from the `backend_status` table first, preserving the existing
+/// subscribe-before-read, dedup-by-last-status ordering; only the live tail
+/// moves off the database.
+///
+/// The methods are concrete (not generic over the payload) so the trait is
+/// object-safe and a [`NotificationTransportConfig`] can hand back one of the
+/// two implementations as `Box<dyn NotificationTransport>` at runtime.
+#[async_trait]
+pub trait NotificationTransport: Send + Sync {
+    /// Publish a status update on the channel for `key`.
+    async fn publish(&self, key: &str, payload: &TimestampedBackendStatus) -> sqlx::Result<()>;
+
+    /// Subscribe to the live stream of status updates for `key`.
+    async fn subscribe(
+        &self,
+        key: &str,
+    ) -> sqlx::Result<Pin<Box<dyn Stream<Item = TimestampedBackendStatus> + Send>>>;
+}
+
+/// Live status updates share the `backend_state` channel namespace with the
+/// existing Postgres path, so `status_stream` history and live tail line up.
+impl NotificationPayload for TimestampedBackendStatus {
+    fn kind() -> &'static str {
+        "backend_state"
+    }
+}
+
+/// Which notification transport the controller uses for live fan-out.
+#[derive(Clone, Debug)]
+pub enum NotificationTransportConfig {
+    /// Postgres LISTEN/NOTIFY (the default, single-primary path).
+    Postgres,
+    /// Redis pub/sub, for scaling fan-out across many controllers.
+    Redis { url: String },
+}
+
+impl Default for NotificationTransportConfig {
+    fn default() -> Self {
+        NotificationTransportConfig::Postgres
+    }
+}
+
+impl NotificationTransportConfig {
+    /// Construct the selected transport. This is the runtime dispatch point the
+    /// object-safe trait enables: the returned box hides which implementation
+    /// backs the live fan-out.
+    pub fn build(&self, db: &PlaneDatabase) -> sqlx::Result<Box<dyn NotificationTransport>> {
+        match self {
+            NotificationTransportConfig::Postgres => {
+                Ok(Box::new(PostgresTransport::new(db.clone())))
+            }
+            NotificationTransportConfig::Redis { url } => {
+                Ok(Box::new(RedisTransport::new(url)?))
+            }
+        }
+    }
+}
+
+/// Channel name for a payload kind and key, e.g. `plane:backend_state:{id}`.
+fn channel_name<P: NotificationPayload>(key: &str) -> String {
+    format!("plane:{}:{}", P::kind(), key)
+}
+
+/// Postgres LISTEN/NOTIFY transport, delegating to the existing `subscribe`
+/// machinery on [`PlaneDatabase`]. Owns a handle so the boxed transport is
+/// `'static`.
+pub struct PostgresTransport {
+    db: PlaneDatabase,
+}
+
+impl PostgresTransport {
+    pub fn new(db: PlaneDatabase) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl NotificationTransport for PostgresTransport {
+    async fn publish(&self, key: &str, payload: &TimestampedBackendStatus) -> sqlx::Result<()> {
+        super::subscribe::emit_with_key(&self.db.pool, key, payload).await
+    }
+
+    async fn subscribe(
+        &self,
+        key: &str,
+    ) -> sqlx::Result<Pin<Box<dyn Stream<Item = TimestampedBackendStatus> + Send>>> {
+        let mut sub = self.db.subscribe_with_key::<TimestampedBackendStatus>(key);
+        let stream = async_stream::stream! {
+            while let Some(item) = sub.next().await {
+                yield item.payload;
+            }
+        };
+        Ok(Box::pin(stream))
+    }
+}
+
+/// Redis pub/sub transport. Publishers `PUBLISH plane:backend_state:{key}` with
+/// the JSON-serialized [`TimestampedBackendStatus`]; subscribers consume the
+/// matching channel.
+pub struct RedisTransport {
+    client: redis::Client,
+}
+
+impl RedisTransport {
+    pub fn new(url: &str) -> sqlx::Result<Self> {
+        let client =
+            redis::Client::open(url).map_err(|e| sqlx::Error::Configuration(Box::new(e)))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl NotificationTransport for RedisTransport {
+    async fn publish(&self, key: &str, payload: &TimestampedBackendStatus) -> sqlx::Result<()> {
+        let channel = channel_name::<TimestampedBackendStatus>(key);
+        let body = serde_json::to_string(payload).map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| sqlx::Error::Io(std::io::Error::other(e)))?;
+
+        redis::cmd("PUBLISH")
+            .arg(&channel)
+            .arg(body)
+            .query_async::<()>(&mut conn)
+            .await
+            .map_err(|e| sqlx::Error::Io(std::io::Error::other(e)))?;
+
+        Ok(())
+    }
+
+    async fn subscribe(
+        &self,
+        key: &str,
+    ) -> sqlx::Result<Pin<Box<dyn Stream<Item = TimestampedBackendStatus> + Send>>> {
+        let channel = channel_name::<TimestampedBackendStatus>(key);
+
+        let mut pubsub = self
+            .client
+            .get_async_pubsub()
+            .await
+            .map_err(|e| sqlx::Error::Io(std::io::Error::other(e)))?;
+        pubsub
+            .subscribe(&channel)
+            .await
+            .map_err(|e| sqlx::Error::Io(std::io::Error::other(e)))?;
+
+        let stream = pubsub.into_on_message().filter_map(|msg| async move {
+            let body: String = msg.get_payload().ok()?;
+            match serde_json::from_str::<TimestampedBackendStatus>(&body) {
+                Ok(payload) => Some(payload),
+                Err(e) => {
+                    tracing::warn!(?e, "Failed to decode Redis notification payload");
+                    None
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
@@ -0,0 +1,160 @@
+//! Prometheus metrics for backend lifecycle and termination pressure.
+//!
+//! The controller already routes every status transition through
+//! [`update_status`](crate::database::backend::BackendDatabase::update_status)
+//! and evaluates termination pressure per drone in
+//! [`termination_candidates`](crate::database::backend::BackendDatabase::termination_candidates);
+//! this module exports that data so operators can alert on spawn rate,
+//! stuck-state backends, and termination pressure. The metrics are served in
+//! Prometheus text format from the controller (see [`metrics_handler`]).
+
+use crate::types::BackendStatus;
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram, register_int_counter_vec, register_int_gauge_vec, Encoder, Histogram,
+    IntCounterVec, IntGaugeVec, TextEncoder,
+};
+
+/// Reason a backend was terminated.
+#[derive(Debug, Clone, Copy)]
+pub enum TerminationReason {
+    IdleTimeout,
+    LifetimeLimit,
+    /// Terminated without an idle/lifetime limit firing (manual stop,
+    /// self-exit, spawn failure, …).
+    Other,
+}
+
+impl TerminationReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            TerminationReason::IdleTimeout => "idle_timeout",
+            TerminationReason::LifetimeLimit => "lifetime_limit",
+            TerminationReason::Other => "other",
+        }
+    }
+
+    /// Attribute a termination to whichever limit predicate actually fired,
+    /// mirroring the predicate in `termination_candidates`. Falls back to
+    /// [`TerminationReason::Other`] when neither limit applies, so backends
+    /// stopped for other reasons aren't mislabeled as idle/lifetime.
+    pub fn attribute(
+        allowed_idle_seconds: Option<i32>,
+        last_keepalive: chrono::DateTime<chrono::Utc>,
+        expiration_time: Option<chrono::DateTime<chrono::Utc>>,
+        as_of: chrono::DateTime<chrono::Utc>,
+    ) -> TerminationReason {
+        let lag = as_of - last_keepalive;
+        let idle_fired = allowed_idle_seconds
+            .is_some_and(|idle| lag > chrono::Duration::seconds(idle as i64));
+        let lifetime_fired = expiration_time.is_some_and(|exp| as_of > exp);
+        if idle_fired {
+            TerminationReason::IdleTimeout
+        } else if lifetime_fired {
+            TerminationReason::LifetimeLimit
+        } else {
+            TerminationReason::Other
+        }
+    }
+}
+
+/// Current number of backends per cluster and status.
+static BACKEND_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "plane_backends",
+        "Number of backends by cluster and status.",
+        &["cluster", "status"]
+    )
+    .expect("metric can be registered")
+});
+
+/// Status transitions, labeled by source and destination status.
+static STATUS_TRANSITIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "plane_backend_status_transitions_total",
+        "Backend status transitions by from/to status.",
+        &["from", "to"]
+    )
+    .expect("metric can be registered")
+});
+
+/// Keepalive lag (seconds since last keepalive) sampled when termination
+/// candidates are evaluated.
+static KEEPALIVE_LAG: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "plane_backend_keepalive_lag_seconds",
+        "Seconds since last keepalive, sampled during termination sweeps.",
+        vec![1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0]
+    )
+    .expect("metric can be registered")
+});
+
+/// Backends terminated, labeled by the limit that fired (`other` when neither
+/// did).
+static TERMINATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "plane_backend_terminations_total",
+        "Backends terminated by idle-timeout, lifetime-limit, or other reason.",
+        &["reason"]
+    )
+    .expect("metric can be registered")
+});
+
+/// Set the live backend count for a cluster/status pair.
+pub fn set_backend_count(cluster: &str, status: BackendStatus, count: i64) {
+    BACKEND_COUNT
+        .with_label_values(&[cluster, &status.to_string()])
+        .set(count);
+}
+
+/// Clear every backend-count series before a census repopulates it, so a
+/// (cluster, status) pair that drops to zero is not left pinned at its old
+/// value.
+pub fn reset_backend_counts() {
+    BACKEND_COUNT.reset();
+}
+
+/// Record a backend status transition.
+pub fn record_status_transition(from: Option<BackendStatus>, to: BackendStatus) {
+    let from = from.map(|s| s.to_string()).unwrap_or_else(|| "none".to_string());
+    STATUS_TRANSITIONS
+        .with_label_values(&[&from, &to.to_string()])
+        .inc();
+}
+
+/// Observe the keepalive lag (in seconds) for a termination candidate.
+pub fn observe_keepalive_lag(seconds: f64) {
+    KEEPALIVE_LAG.observe(seconds);
+}
+
+/// Record that a backend was terminated for the given reason.
+pub fn record_termination(reason: TerminationReason) {
+    TERMINATIONS.with_label_values(&[reason.as_str()]).inc();
+}
+
+/// Router exposing the Prometheus scrape endpoint. Nested into the
+/// controller's HTTP router so the metrics are actually reachable at
+/// `/metrics`.
+pub fn routes() -> axum::Router {
+    axum::Router::new().route("/metrics", axum::routing::get(metrics_handler))
+}
+
+/// Axum handler that serves the registered metrics for Prometheus scraping.
+pub async fn metrics_handler() -> impl axum::response::IntoResponse {
+    (
+        [(axum::http::header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        render(),
+    )
+}
+
+/// Render all registered metrics in Prometheus text format.
+pub fn render() -> String {
+    let encoder = TextEncoder::new();
+    let metric_families = prometheus::gather();
+    let mut buffer = Vec::new();
+    if let Err(err) = encoder.encode(&metric_families, &mut buffer) {
+        tracing::error!(?err, "Failed to encode metrics.");
+        return String::new();
+    }
+    String::from_utf8_lossy(&buffer).into_owned()
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Lifecycle status of a backend.
+///
+/// The variants are declared in lifecycle order, so the derived [`Ord`] gives
+/// the monotonic ordering that `status_stream` relies on to deduplicate the
+/// historical read against the live subscription. The same order is mirrored
+/// by the native Postgres `backend_status` enum (see the
+/// `backend_status_enum` migration), which lets the database compare and
+/// reject statuses by their natural order. The `#[sqlx(type_name = ...)]`
+/// attribute binds the Rust enum to that Postgres type; the explicit
+/// per-variant renames keep the wire, SQL, and text representations identical
+/// (a plain `rename_all` cannot produce the hyphenated `hard-terminating`).
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "backend_status", rename_all = "lowercase")]
+pub enum BackendStatus {
+    Scheduled,
+    Loading,
+    Starting,
+    Waiting,
+    Ready,
+    Terminating,
+    #[serde(rename = "hard-terminating")]
+    #[sqlx(rename = "hard-terminating")]
+    HardTerminating,
+    Terminated,
+}
+
+impl BackendStatus {
+    /// The canonical string form, matching the Postgres enum label.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BackendStatus::Scheduled => "scheduled",
+            BackendStatus::Loading => "loading",
+            BackendStatus::Starting => "starting",
+            BackendStatus::Waiting => "waiting",
+            BackendStatus::Ready => "ready",
+            BackendStatus::Terminating => "terminating",
+            BackendStatus::HardTerminating => "hard-terminating",
+            BackendStatus::Terminated => "terminated",
+        }
+    }
+}
+
+impl fmt::Display for BackendStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BackendStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "scheduled" => Ok(BackendStatus::Scheduled),
+            "loading" => Ok(BackendStatus::Loading),
+            "starting" => Ok(BackendStatus::Starting),
+            "waiting" => Ok(BackendStatus::Waiting),
+            "ready" => Ok(BackendStatus::Ready),
+            "terminating" => Ok(BackendStatus::Terminating),
+            "hard-terminating" => Ok(BackendStatus::HardTerminating),
+            "terminated" => Ok(BackendStatus::Terminated),
+            other => Err(format!("Invalid backend status: {other}")),
+        }
+    }
+}
+
+impl TryFrom<String> for BackendStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}